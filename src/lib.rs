@@ -1,6 +1,75 @@
+use std::any::{Any, TypeId};
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::fmt::Debug;
 use std::future::Future;
+use std::hash::{Hash, Hasher};
+use std::rc::{Rc, Weak};
+use std::time::Duration;
 use dioxus::prelude::*;
+use futures_util::future::FutureExt;
+
+/// Retry/backoff policy for [`FutureHook::new_with_retry`].
+#[derive(Copy, Clone)]
+pub struct RetryPolicy {
+  /// Maximum number of automatic retries before giving up and leaving the hook in `Error`.
+  pub max_attempts: u32,
+  /// Delay before the first retry.
+  pub base_delay: Duration,
+  /// Upper bound for the computed delay, no matter how many attempts have been made.
+  pub max_delay: Duration,
+  /// Whether to add up to `delay` of random jitter on top of the computed backoff.
+  pub jitter: bool,
+}
+
+#[derive(Copy, Clone)]
+struct RetryState<'a> {
+  policy: RetryPolicy,
+  attempts: &'a UseState<u32>,
+  next_retry_at: &'a UseState<Option<std::time::Instant>>,
+  /// The state this hook was in the last time `drive_retry()` ran, so a retry is only
+  /// ever scheduled once per transition into `Error`, not on every render that observes it.
+  last_seen: &'a UseState<FutureState>,
+}
+
+/// Marker error produced internally by [`FutureHook::new_with_timeout`] when the wrapped
+/// future doesn't resolve before its deadline.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TimeoutError;
+
+impl std::fmt::Display for TimeoutError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "future timed out")
+  }
+}
+
+impl std::error::Error for TimeoutError {}
+
+#[derive(Copy, Clone)]
+struct PollingState<'a> {
+  paused: &'a UseState<bool>,
+}
+
+type BoxFuture<T, E> = std::pin::Pin<Box<dyn Future<Output = Result<T, E>>>>;
+type SharedFuture<T, E> = futures_util::future::Shared<BoxFuture<T, E>>;
+
+thread_local! {
+  /// Thread-local registry of in-flight [`FutureHook::shared`] futures. Dioxus apps are
+  /// single-threaded on every target they support (wasm32, or a single-threaded desktop/CLI
+  /// host), so one thread-local registry is, in practice, the whole app's registry. Entries
+  /// are bucketed by `(TypeId::of::<K>(), hash(key))` for an O(1) average lookup, but each
+  /// bucket stores the real key alongside the weak handle, so two keys that merely
+  /// hash-collide are never mistaken for each other. Dead weak handles are pruned from a
+  /// bucket only when that bucket is touched again; a bucket whose key is never used again
+  /// keeps its entries (dead handles included) for the rest of the app's lifetime.
+  static SHARED_REGISTRY: RefCell<HashMap<(TypeId, u64), Vec<Box<dyn Any>>>> = RefCell::new(HashMap::new());
+}
+
+fn shared_registry_bucket<K: Hash + 'static>(key: &K) -> (TypeId, u64) {
+  let mut hasher = std::collections::hash_map::DefaultHasher::new();
+  key.hash(&mut hasher);
+  (TypeId::of::<K>(), hasher.finish())
+}
 
 /// Optional future hook.
 #[derive(Copy, Clone)]
@@ -9,17 +78,39 @@ pub struct FutureHook<'a, T, E>
     T: 'static + ?Sized + Clone,
     E: 'static + ?Sized + Clone + Debug,
 {
+  scope: Scope<'a>,
   future: &'a UseFuture<Result<T, E>>,
   outdated_marker: &'a UseState<bool>,
+  retry: Option<RetryState<'a>>,
+  timed_out_marker: Option<&'a UseState<bool>>,
+  polling: Option<PollingState<'a>>,
+  /// Every [`FutureHook::on_transition`] subscription registered on this hook so far this
+  /// render. Cleared at the start of each render (in [`FutureHook::new`] and the other
+  /// constructors) so it reflects exactly this render's `on_transition` calls, then appended
+  /// to by each of those calls - supporting more than one observer per hook.
+  observers: &'a RefCell<Vec<ObserverState<'a, T, E>>>,
 }
 
-#[derive(PartialEq, Eq)]
+#[derive(PartialEq, Eq, Clone, Copy)]
 pub enum FutureState {
   Empty,
   Ready,
   Error,
   Outdated,
   Reloading,
+  TimedOut,
+}
+
+type TransitionCallback<T, E> = Box<dyn Fn(FutureState, FutureState, Option<&Result<T, E>>)>;
+
+#[derive(Copy, Clone)]
+struct ObserverState<'a, T, E>
+  where
+    T: 'static + Clone,
+    E: 'static + Clone + Debug,
+{
+  last_state: &'a UseState<FutureState>,
+  subscribers: &'a RefCell<Vec<TransitionCallback<T, E>>>,
 }
 
 #[derive(PartialEq, Eq)]
@@ -55,24 +146,383 @@ impl<'a, T, E> FutureHook<'a, T, E>
     fut: impl FnOnce(D::Out) -> F
   ) -> Self {
     let outdated = startup_guard == StartupGuard::Enable;
+    let observers = cx.use_hook(|| RefCell::new(Vec::new()));
+    observers.borrow_mut().clear();
     Self {
+      scope: cx,
       future: use_future(cx, dependencies, fut),
       outdated_marker: use_state(cx, || outdated),
+      retry: None,
+      timed_out_marker: None,
+      polling: None,
+      observers,
+    }
+  }
+
+  /// Creates a new future hook that refreshes itself in the background every `interval`,
+  /// without the caller having to wire a manual timer plus `set_outdated()`/`fetch()`.
+  ///
+  /// Example:
+  /// ```rust
+  /// use std::time::Duration;
+  /// use dioxus_v04_optional_hooks::FutureHook;
+  /// ...
+  /// let generate_fut = FutureHook::new_polling(cx, (dependency_state_hook,), |(dependency_state_hook,)| {
+  ///   async move {
+  ///     some_func(*dependency_state_hook).await
+  ///   }
+  /// }, Duration::from_secs(30));
+  /// ```
+  pub fn new_polling<
+    D: UseFutureDep,
+    F: Future<Output = Result<T, E>> + 'static
+  >(
+    cx: Scope<'a>,
+    dependencies: D,
+    fut: impl FnOnce(D::Out) -> F,
+    interval: Duration,
+  ) -> Self {
+    // `StartupGuard::Disable`: `use_future` already runs the initial fetch on mount, so
+    // starting `outdated_marker` at `true` would make the `hook.fetch()` below restart it a
+    // second time as soon as that first run completed. `fetch()` itself still has to stay -
+    // it's what turns the outdated_marker flip from a background tick into an actual
+    // restart on the next render - but with `Disable` it's a no-op until the first tick fires.
+    let mut hook = Self::new(cx, StartupGuard::Disable, dependencies, fut);
+    let paused = use_state(cx, || false);
+    hook.polling = Some(PollingState { paused });
+
+    // `UseFuture` has no owned, `'static` handle we could call `restart()` on from a
+    // background task, so the loop only flips the (clonable, owned) `outdated_marker` on
+    // a tick; the actual restart happens back on the render path via `fetch()`, which
+    // already refuses to restart while a fetch is still in flight.
+    let paused_for_task = paused.clone();
+    let outdated_marker = hook.outdated_marker.clone();
+    cx.use_hook(|| {
+      cx.spawn(async move {
+        loop {
+          Self::platform_sleep(interval).await;
+          if *paused_for_task.current() { continue }
+          outdated_marker.set(true);
+        }
+      });
+    });
+    hook.fetch();
+    hook
+  }
+
+  /// Pauses the background polling started by [`FutureHook::new_polling`]; a no-op otherwise.
+  pub fn pause_polling(&self) {
+    if let Some(polling) = self.polling { polling.paused.set(true); }
+  }
+
+  /// Resumes the background polling started by [`FutureHook::new_polling`]; a no-op otherwise.
+  pub fn resume_polling(&self) {
+    if let Some(polling) = self.polling { polling.paused.set(false); }
+  }
+
+  /// Creates a new future hook that resolves to [`FutureState::TimedOut`] instead of sitting
+  /// in [`FutureState::Empty`] forever when the future doesn't complete within `timeout`.
+  ///
+  /// Example:
+  /// ```rust
+  /// use std::time::Duration;
+  /// use dioxus_v04_optional_hooks::{FutureHook, StartupGuard};
+  /// ...
+  /// let generate_fut = FutureHook::new_with_timeout(cx, StartupGuard::Enable, (dependency_state_hook,), |(dependency_state_hook,)| {
+  ///   async move {
+  ///     some_func(*dependency_state_hook).await
+  ///   }
+  /// }, Duration::from_secs(10));
+  /// ```
+  pub fn new_with_timeout<
+    D: UseFutureDep,
+    F: Future<Output = Result<T, E>> + 'static
+  >(
+    cx: Scope<'a>,
+    startup_guard: StartupGuard,
+    dependencies: D,
+    fut: impl FnOnce(D::Out) -> F,
+    timeout: Duration,
+  ) -> Self
+    where E: From<TimeoutError>
+  {
+    let outdated = startup_guard == StartupGuard::Enable;
+    let timed_out = use_state(cx, || false);
+    let timed_out_for_fut = timed_out.clone();
+    let future = use_future(cx, dependencies, move |deps| {
+      let inner = fut(deps);
+      let timed_out = timed_out_for_fut.clone();
+      async move {
+        // A dependency change restarts this future directly, without going through
+        // `restart()`, so reset the flag here too - otherwise a stale `true` from a
+        // previous timeout would mislabel this run's genuine `Err` as `TimedOut`.
+        timed_out.set(false);
+        futures_util::pin_mut!(inner);
+        match futures_util::future::select(inner, Box::pin(Self::platform_sleep(timeout))).await {
+          futures_util::future::Either::Left((res, _)) => res,
+          futures_util::future::Either::Right((_, _)) => {
+            timed_out.set(true);
+            Err(E::from(TimeoutError))
+          },
+        }
+      }
+    });
+    let observers = cx.use_hook(|| RefCell::new(Vec::new()));
+    observers.borrow_mut().clear();
+    Self {
+      scope: cx,
+      future,
+      outdated_marker: use_state(cx, || outdated),
+      retry: None,
+      timed_out_marker: Some(timed_out),
+      polling: None,
+      observers,
+    }
+  }
+
+  /// Sleeps for `duration`, regardless of target platform.
+  async fn platform_sleep(duration: Duration) {
+    #[cfg(target_arch = "wasm32")]
+    gloo_timers::future::sleep(duration).await;
+    #[cfg(not(target_arch = "wasm32"))]
+    tokio::time::sleep(duration).await;
+  }
+
+  /// Creates a new future hook that automatically retries with exponential backoff
+  /// whenever it completes with `Err`, up to `policy.max_attempts` times.
+  ///
+  /// Example:
+  /// ```rust
+  /// use std::time::Duration;
+  /// use dioxus_v04_optional_hooks::{FutureHook, RetryPolicy, StartupGuard};
+  /// ...
+  /// let generate_fut = FutureHook::new_with_retry(cx, StartupGuard::Enable, (dependency_state_hook,), |(dependency_state_hook,)| {
+  ///   async move {
+  ///     some_func(*dependency_state_hook).await
+  ///   }
+  /// }, RetryPolicy { max_attempts: 5, base_delay: Duration::from_secs(1), max_delay: Duration::from_secs(30), jitter: true });
+  /// ```
+  pub fn new_with_retry<
+    D: UseFutureDep,
+    F: Future<Output = Result<T, E>> + 'static
+  >(
+    cx: Scope<'a>,
+    startup_guard: StartupGuard,
+    dependencies: D,
+    fut: impl FnOnce(D::Out) -> F,
+    policy: RetryPolicy,
+  ) -> Self {
+    let mut hook = Self::new(cx, startup_guard, dependencies, fut);
+    hook.retry = Some(RetryState {
+      policy,
+      attempts: use_state(cx, || 0u32),
+      next_retry_at: use_state(cx, || None),
+      last_seen: use_state(cx, || hook.raw_state()),
+    });
+    hook.drive_retry();
+    hook
+  }
+
+  /// Creates a future hook deduplicated across every component that calls `shared` with an
+  /// equal `key`: concurrent hooks subscribe to the same in-flight future and reuse its
+  /// resolved value instead of each spawning their own request. The registry holds only weak
+  /// handles to the underlying future, so once the last subscriber drops it, the future is
+  /// cancelled rather than left running unobserved. Each hook keeps its own `outdated_marker`,
+  /// so individual components can still call `set_outdated()`/`fetch()` to force a refresh.
+  ///
+  /// Example:
+  /// ```rust
+  /// use dioxus_v04_optional_hooks::FutureHook;
+  /// ...
+  /// let generate_fut = FutureHook::shared(cx, dependency_state_hook.get().clone(), (dependency_state_hook,), |(dependency_state_hook,)| {
+  ///   async move {
+  ///     some_func(*dependency_state_hook).await
+  ///   }
+  /// });
+  /// ```
+  pub fn shared<
+    K: Hash + Eq + Clone + 'static,
+    D: UseFutureDep,
+    F: Future<Output = Result<T, E>> + 'static
+  >(
+    cx: Scope<'a>,
+    key: K,
+    dependencies: D,
+    fut: impl FnOnce(D::Out) -> F,
+  ) -> Self {
+    let future = use_future(cx, dependencies, move |deps| {
+      let bucket_key = shared_registry_bucket(&key);
+      let inner: BoxFuture<T, E> = Box::pin(fut(deps));
+      async move {
+        let shared = SHARED_REGISTRY.with(|registry| {
+          let mut registry = registry.borrow_mut();
+          let bucket = registry.entry(bucket_key).or_insert_with(Vec::new);
+          bucket.retain(|entry| {
+            entry.downcast_ref::<(K, Weak<SharedFuture<T, E>>)>()
+              .map(|(_, weak)| weak.strong_count() > 0)
+              .unwrap_or(true)
+          });
+          let existing = bucket.iter()
+            .filter_map(|entry| entry.downcast_ref::<(K, Weak<SharedFuture<T, E>>)>())
+            .find(|entry| entry.0 == key)
+            .and_then(|(_, weak)| weak.upgrade());
+          match existing {
+            Some(shared) => shared,
+            None => {
+              let shared: Rc<SharedFuture<T, E>> = Rc::new(inner.shared());
+              bucket.push(Box::new((key, Rc::downgrade(&shared))));
+              shared
+            },
+          }
+        });
+        (*shared).clone().await
+      }
+    });
+    let observers = cx.use_hook(|| RefCell::new(Vec::new()));
+    observers.borrow_mut().clear();
+    Self {
+      scope: cx,
+      future,
+      outdated_marker: use_state(cx, || false),
+      retry: None,
+      timed_out_marker: None,
+      polling: None,
+      observers,
     }
   }
 
   /// Extends the standard future states by adding one more.
   pub fn check_state(&self) -> FutureState {
-    let val = match self.future.state() {
+    let mut val = self.raw_state();
+    if val == FutureState::Error {
+      if let Some(timed_out) = self.timed_out_marker {
+        if **timed_out { val = FutureState::TimedOut; }
+      }
+    }
+    if (val == FutureState::Ready || val == FutureState::Error) && self.is_outdated() {
+      val = FutureState::Outdated;
+    }
+    val
+  }
+
+  /// The raw, un-annotated future state, with none of `Outdated`/`TimedOut` applied.
+  fn raw_state(&self) -> FutureState {
+    match self.future.state() {
       UseFutureState::Pending => FutureState::Empty,
       UseFutureState::Complete(Ok(_)) => FutureState::Ready,
       UseFutureState::Complete(Err(_)) => FutureState::Error,
       UseFutureState::Reloading(_) => FutureState::Reloading,
-    };
-    if (val == FutureState::Ready || val == FutureState::Error) && self.is_outdated() {
-      return FutureState::Outdated
     }
-    val
+  }
+
+  /// Registers a closure invoked whenever this hook's `FutureState` changes, so callers don't
+  /// have to diff the state by hand on every render (e.g. to show a toast on `Ready`/`Error`,
+  /// or send analytics on `Reloading`). Must be called unconditionally on every render of the
+  /// component, like any other hook; calling it more than once on the same hook (in the same
+  /// render) registers an additional, independent observer rather than replacing the last one.
+  ///
+  /// Example:
+  /// ```rust
+  /// use dioxus_v04_optional_hooks::{FutureHook, FutureState, StartupGuard};
+  /// ...
+  /// let mut generate_fut = FutureHook::new(cx, StartupGuard::Enable, (), |()| async move { Ok(1) });
+  /// generate_fut.on_transition(cx, |_from, to, _value| {
+  ///   if to == FutureState::Error { /* show a toast */ }
+  /// });
+  /// ```
+  pub fn on_transition(&mut self, cx: Scope<'a>, callback: impl Fn(FutureState, FutureState, Option<&Result<T, E>>) + 'static) {
+    let last_state = use_state(cx, || self.raw_state());
+    let subscribers = cx.use_hook(|| RefCell::new(Vec::<TransitionCallback<T, E>>::new()));
+    subscribers.borrow_mut().clear();
+    subscribers.borrow_mut().push(Box::new(callback));
+    self.observers.borrow_mut().push(ObserverState { last_state, subscribers });
+    self.dispatch_transitions();
+  }
+
+  /// Invokes every registered [`FutureHook::on_transition`] subscriber whose observer hasn't
+  /// yet seen the current state. Called once per `on_transition` call (itself required to be
+  /// called once per render, like any other hook); dispatching to every observer registered
+  /// so far, not just the one just added, is safe because an observer that already saw this
+  /// state is a no-op here, so a subscriber is still never invoked more than once per transition.
+  fn dispatch_transitions(&self) {
+    let new_state = self.check_state();
+    let value = self.future.value();
+    for observer in self.observers.borrow().iter() {
+      let previous = *observer.last_state.current();
+      if previous == new_state { continue }
+      for subscriber in observer.subscribers.borrow().iter() {
+        subscriber(previous, new_state, value);
+      }
+      observer.last_state.set(new_state);
+    }
+  }
+
+  /// Schedules or clears the automatic retry depending on the freshly observed state. Must
+  /// be called once per render (it's driven from the tail of `new_with_retry`, which itself
+  /// runs exactly once per render) so a single transition into `Error` is only ever acted on
+  /// once, rather than every time something calls `check_state()`.
+  fn drive_retry(&self) {
+    let Some(retry) = self.retry else { return };
+
+    // A previous retry timer may have flipped `outdated_marker`; surface that through the
+    // existing outdated/fetch path, which already refuses to restart mid-reload.
+    if self.is_outdated() { self.fetch(); }
+
+    let current = self.raw_state();
+    if **retry.last_seen == current { return }
+    retry.last_seen.set(current);
+
+    match current {
+      FutureState::Ready => {
+        retry.attempts.set(0);
+        retry.next_retry_at.set(None);
+      },
+      FutureState::Error => {
+        if **retry.attempts >= retry.policy.max_attempts { return }
+
+        let attempt = **retry.attempts;
+        let mut delay = retry.policy.base_delay.saturating_mul(1 << attempt.min(31));
+        if delay > retry.policy.max_delay { delay = retry.policy.max_delay; }
+        if retry.policy.jitter {
+          let jitter_ms = (Self::pseudo_random() * delay.as_millis() as f64) as u64;
+          delay += Duration::from_millis(jitter_ms);
+        }
+        retry.next_retry_at.set(Some(std::time::Instant::now() + delay));
+
+        // `UseFuture` has no owned, `'static` handle to call `restart()` on from a
+        // background task; only mark the hook outdated, and let `drive_retry()` pick that
+        // up through `fetch()` on the next render, same as `new_polling` does.
+        let attempts = retry.attempts.clone();
+        let next_retry_at = retry.next_retry_at.clone();
+        let outdated_marker = self.outdated_marker.clone();
+        self.scope.spawn(async move {
+          Self::platform_sleep(delay).await;
+          attempts.set(*attempts + 1);
+          next_retry_at.set(None);
+          outdated_marker.set(true);
+        });
+      },
+      _ => {},
+    }
+  }
+
+  /// Cheap, dependency-free source of randomness in `[0.0, 1.0)`, good enough for jitter.
+  fn pseudo_random() -> f64 {
+    let nanos = std::time::SystemTime::now()
+      .duration_since(std::time::UNIX_EPOCH)
+      .unwrap_or_default()
+      .subsec_nanos();
+    (nanos % 1_000_000) as f64 / 1_000_000.0
+  }
+
+  /// Returns how many retry attempts have been made so far for this hook.
+  pub fn attempts(&self) -> u32 {
+    self.retry.map(|retry| **retry.attempts).unwrap_or(0)
+  }
+
+  /// Returns the instant at which the next automatic retry is scheduled to fire, if any.
+  pub fn next_retry_at(&self) -> Option<std::time::Instant> {
+    self.retry.and_then(|retry| **retry.next_retry_at)
   }
 
   /// Reads the future value, if any.
@@ -80,7 +530,7 @@ impl<'a, T, E> FutureHook<'a, T, E>
     if self.is_outdated() { return None }
     if !allow_cache_while_reloading {
       match self.check_state() {
-        FutureState::Empty | FutureState::Reloading | FutureState::Error | FutureState::Outdated => { None },
+        FutureState::Empty | FutureState::Reloading | FutureState::Error | FutureState::Outdated | FutureState::TimedOut => { None },
         FutureState::Ready => {
           let val = self.future.value().as_ref().unwrap().as_ref().unwrap();
           Some(val)
@@ -88,7 +538,7 @@ impl<'a, T, E> FutureHook<'a, T, E>
       }
     } else {
       match self.check_state() {
-        FutureState::Empty | FutureState::Error => { None },
+        FutureState::Empty | FutureState::Error | FutureState::TimedOut => { None },
         FutureState::Ready | FutureState::Reloading => {
           let val_p = self.future.value();
           let val = val_p.as_ref().unwrap();
@@ -123,12 +573,23 @@ impl<'a, T, E> FutureHook<'a, T, E>
     if self.check_state() == FutureState::Empty || self.check_state() == FutureState::Reloading { return }
 
     self.outdated_marker.set(false);
+    if let Some(timed_out) = self.timed_out_marker { timed_out.set(false); }
     self.future.restart();
   }
 
-  /// Restarts the future only if it's outdated.
+  /// Restarts the future if it's outdated, or if it's sitting in `TimedOut` - a timed-out
+  /// future isn't necessarily outdated, but must still be restartable via `fetch()` the same
+  /// as `restart()` already allows directly.
   pub fn fetch(&self) {
-    if self.is_outdated() { self.restart(); }
+    if self.is_outdated() || self.check_state() == FutureState::TimedOut { self.restart(); }
+  }
+
+  /// Returns the current `FutureState`, exactly as [`FutureHook::check_state`] does. It does
+  /// not poll the underlying task: dioxus 0.4's `UseFuture` exposes no API to advance its
+  /// inner task ahead of the scheduler's own wakeup, so a still-pending future does not
+  /// resolve any sooner for calling this.
+  pub fn poll_state(&self) -> FutureState {
+    self.check_state()
   }
 
   /// Checks if the future is outdated.